@@ -0,0 +1,186 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::TcpStream;
+use std::time::Duration;
+use tungstenite::{client::connect_with_config, protocol::WebSocketConfig, stream::MaybeTlsStream, Message, WebSocket};
+
+/// Timeout applied to every HTTP call and WebSocket read this client
+/// makes, so a browser that stops responding mid-poll can't hang the
+/// tracking loop (and therefore the GUI) indefinitely.
+const CDP_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// One entry from `GET http://127.0.0.1:<port>/json`.
+#[derive(Debug, Clone, Deserialize)]
+struct CdpTarget {
+    id: String,
+    #[serde(rename = "type")]
+    target_type: String,
+    title: String,
+    url: String,
+}
+
+/// Relevant part of `GET http://127.0.0.1:<port>/json/version`.
+#[derive(Debug, Deserialize)]
+struct VersionInfo {
+    #[serde(rename = "webSocketDebuggerUrl")]
+    websocket_debugger_url: String,
+}
+
+/// Minimal Chrome DevTools Protocol client used to read the active tab's
+/// URL and title from a Chromium browser launched with
+/// `--remote-debugging-port=<port>`. This avoids shelling out to
+/// `osascript` on every poll and works across Chromium-based browsers.
+pub struct CdpClient {
+    port: u16,
+}
+
+impl CdpClient {
+    pub fn new(port: u16) -> Self {
+        Self { port }
+    }
+
+    /// Returns `(url, title)` of the focused page target, if the browser
+    /// is reachable and has at least one open tab.
+    pub fn active_page(&self) -> Option<(String, Option<String>)> {
+        let target = self.active_page_target()?;
+
+        match self.navigation_entry(&target.id) {
+            Some((url, title)) => Some((url, Some(title))),
+            None => Some((target.url, Some(target.title))),
+        }
+    }
+
+    /// Picks the `"page"` target that's actually in front. With a single
+    /// open tab there's nothing to disambiguate; otherwise each
+    /// candidate's owning window is checked over the browser-level
+    /// devtools socket via `Browser.getWindowForTarget` /
+    /// `Browser.getWindowBounds`, and the first one that isn't minimized
+    /// wins. Falls back to `/json`'s listing order if the browser socket
+    /// can't be reached at all.
+    fn active_page_target(&self) -> Option<CdpTarget> {
+        let pages: Vec<CdpTarget> = self
+            .list_targets()?
+            .into_iter()
+            .filter(|t| t.target_type == "page")
+            .collect();
+
+        if pages.len() > 1 {
+            if let Some(mut browser_socket) = self.connect_browser_socket() {
+                for (i, target) in pages.iter().enumerate() {
+                    if self.window_is_visible(&mut browser_socket, &target.id, i as u64) == Some(true) {
+                        return Some(target.clone());
+                    }
+                }
+            }
+        }
+
+        pages.into_iter().next()
+    }
+
+    fn list_targets(&self) -> Option<Vec<CdpTarget>> {
+        let url = format!("http://127.0.0.1:{}/json", self.port);
+        let body = ureq::get(&url).timeout(CDP_TIMEOUT).call().ok()?.into_string().ok()?;
+
+        serde_json::from_str(&body).ok()
+    }
+
+    fn connect_browser_socket(&self) -> Option<WebSocket<MaybeTlsStream<TcpStream>>> {
+        let url = format!("http://127.0.0.1:{}/json/version", self.port);
+        let body = ureq::get(&url).timeout(CDP_TIMEOUT).call().ok()?.into_string().ok()?;
+        let info: VersionInfo = serde_json::from_str(&body).ok()?;
+
+        connect_with_read_timeout(&info.websocket_debugger_url)
+    }
+
+    /// `true` if `target_id`'s owning window is not minimized. `request_id` only needs to be unique per call on a shared
+    /// socket, since each `Browser.*` round trip here uses two ids.
+    fn window_is_visible(
+        &self,
+        socket: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+        target_id: &str,
+        request_id: u64,
+    ) -> Option<bool> {
+        let window = send_request(
+            socket,
+            request_id * 2,
+            "Browser.getWindowForTarget",
+            json!({ "targetId": target_id }),
+        )?;
+        let window_id = window.get("windowId")?.as_u64()?;
+
+        let bounds = send_request(
+            socket,
+            request_id * 2 + 1,
+            "Browser.getWindowBounds",
+            json!({ "windowId": window_id }),
+        )?;
+        let state = bounds.get("bounds")?.get("windowState")?.as_str()?;
+
+        Some(state != "minimized")
+    }
+
+    /// Reads the current navigation history entry for a page target over
+    /// its devtools WebSocket, giving the most up to date URL/title.
+    fn navigation_entry(&self, target_id: &str) -> Option<(String, String)> {
+        let ws_url = format!("ws://127.0.0.1:{}/devtools/page/{}", self.port, target_id);
+        let mut socket = connect_with_read_timeout(&ws_url)?;
+
+        let result = send_request(&mut socket, 1, "Page.getNavigationHistory", json!({}));
+        let _ = socket.close(None);
+        let result = result?;
+
+        let entries = result.get("entries")?.as_array()?;
+        let current_index = result.get("currentIndex")?.as_u64()? as usize;
+        let entry = entries.get(current_index)?;
+
+        let url = entry.get("url")?.as_str()?.to_string();
+        let title = entry.get("title")?.as_str().unwrap_or_default().to_string();
+        Some((url, title))
+    }
+}
+
+/// Connects to a devtools WebSocket and, for plain (non-TLS) sockets —
+/// the only kind `127.0.0.1` ever needs — applies [`CDP_TIMEOUT`] to
+/// reads so an unresponsive tab can't block the caller forever.
+fn connect_with_read_timeout(url: &str) -> Option<WebSocket<MaybeTlsStream<TcpStream>>> {
+    let (socket, _) = connect_with_config(url, Some(WebSocketConfig::default()), 3).ok()?;
+
+    if let MaybeTlsStream::Plain(tcp) = socket.get_ref() {
+        let _ = tcp.set_read_timeout(Some(CDP_TIMEOUT));
+    }
+
+    Some(socket)
+}
+
+/// Sends a single JSON-RPC request and waits for the response with the
+/// matching `id`, skipping over any unrelated event notifications the
+/// browser sends on the same socket in the meantime. Bounded by the
+/// socket's read timeout, so a browser that goes quiet mid-call returns
+/// `None` instead of hanging.
+fn send_request(
+    socket: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+    id: u64,
+    method: &str,
+    params: Value,
+) -> Option<Value> {
+    let request = json!({ "id": id, "method": method, "params": params }).to_string();
+    socket.send(Message::Text(request.into())).ok()?;
+
+    loop {
+        let text = socket.read().ok()?.into_text().ok()?;
+        let parsed: Value = serde_json::from_str(&text).ok()?;
+
+        if parsed.get("id").and_then(Value::as_u64) == Some(id) {
+            return parsed.get("result").cloned();
+        }
+    }
+}
+
+/// Reads `--remote-debugging-port=<port>` from the process arguments, if
+/// present, so the CDP path only activates when the user opted in.
+pub fn debugging_port_from_args() -> Option<u16> {
+    std::env::args().find_map(|arg| {
+        arg.strip_prefix("--remote-debugging-port=")
+            .and_then(|port| port.parse().ok())
+    })
+}
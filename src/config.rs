@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// User-editable categorization rules and privacy settings, loaded from
+/// the platform's config directory (e.g. `~/.config/activity_tracker` on
+/// Linux, `~/Library/Application Support` on macOS, `%APPDATA%` on
+/// Windows). Mirrors the `load_or_default` pattern used for other
+/// per-user state: read the file if it exists, otherwise write out
+/// sensible defaults so the user has something to edit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_categories")]
+    pub categories: Vec<CategoryRule>,
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
+}
+
+/// Maps apps or sites to a category. At least one of `bundle_id_glob` /
+/// `url_host_glob` should be set; rules are tried in order and the first
+/// match wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRule {
+    #[serde(default)]
+    pub bundle_id_glob: Option<String>,
+    #[serde(default)]
+    pub url_host_glob: Option<String>,
+    pub category: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlCapture {
+    /// Store the full URL, including path and query string.
+    #[default]
+    Full,
+    /// Strip everything but the host, e.g. `https://example.com/path?x=1` -> `example.com`.
+    HostOnly,
+    /// Don't store a URL at all.
+    None,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivacyConfig {
+    #[serde(default)]
+    pub url_capture: UrlCapture,
+    /// Hosts that are never recorded, regardless of `url_capture`.
+    #[serde(default)]
+    pub denylist: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            categories: default_categories(),
+            privacy: PrivacyConfig::default(),
+        }
+    }
+}
+
+fn default_categories() -> Vec<CategoryRule> {
+    // Mirrors the bundle ids the tracker has always recognized, just made
+    // user-editable instead of baked into a `match`.
+    [
+        ("com.google.Chrome", "Browser"),
+        ("com.google.Chrome.canary", "Browser"),
+        ("com.apple.Safari", "Browser"),
+        ("com.brave.Browser", "Browser"),
+        ("com.microsoft.edgemac", "Browser"),
+        ("com.apple.Terminal", "Terminal"),
+        ("com.apple.iTerm2", "Terminal"),
+        ("com.apple.mail", "Email"),
+        ("com.microsoft.Outlook", "Email"),
+        ("com.apple.Slack", "Communication"),
+        ("com.microsoft.Teams", "Communication"),
+        ("com.apple.Notes", "Productivity"),
+        ("com.apple.TextEdit", "Productivity"),
+    ]
+    .into_iter()
+    .map(|(bundle_id, category)| CategoryRule {
+        bundle_id_glob: Some(bundle_id.to_string()),
+        url_host_glob: None,
+        category: category.to_string(),
+    })
+    .collect()
+}
+
+impl Config {
+    pub fn config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("activity_tracker").join("config.toml"))
+    }
+
+    pub fn load_or_default() -> Self {
+        let Some(path) = Self::config_path() else {
+            eprintln!("Warning: Could not determine config directory; using default categorization.");
+            return Self::default();
+        };
+
+        if !path.exists() {
+            let defaults = Self::default();
+            if let Err(e) = defaults.save(&path) {
+                eprintln!("Warning: Could not write default config to {}: {}", path.display(), e);
+            }
+            return defaults;
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Warning: Could not parse config at {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) => {
+                eprintln!("Warning: Could not read config at {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let contents =
+            toml::to_string_pretty(self).map_err(|e| format!("Failed to serialize config: {}", e))?;
+        fs::write(path, contents).map_err(|e| format!("Failed to write config: {}", e))
+    }
+
+    /// Returns the category for an app/tab. Rules are tried in order and
+    /// each rule checks the `url` host (if any) before the bundle id, so a
+    /// site-specific rule overrides a browser's generic category only when
+    /// it is listed before that browser's bundle-id rule.
+    pub fn categorize(&self, bundle_id: &str, url: Option<&str>) -> Option<String> {
+        let host = url.and_then(extract_host);
+
+        self.categories.iter().find_map(|rule| {
+            if let (Some(glob), Some(host)) = (&rule.url_host_glob, host.as_deref()) {
+                if glob_match(glob, host) {
+                    return Some(rule.category.clone());
+                }
+            }
+
+            if let Some(glob) = &rule.bundle_id_glob {
+                if glob_match(glob, bundle_id) {
+                    return Some(rule.category.clone());
+                }
+            }
+
+            None
+        })
+    }
+
+    /// Applies the privacy mode to a captured URL before it's written to
+    /// the store: denylisted hosts are dropped outright, then the
+    /// remaining URL is kept, trimmed to its host, or dropped entirely
+    /// depending on `privacy.url_capture`.
+    pub fn redact_url(&self, url: Option<String>) -> Option<String> {
+        let url = url?;
+        let host = extract_host(&url);
+
+        if let Some(host) = &host {
+            if self
+                .privacy
+                .denylist
+                .iter()
+                .any(|denied| denied.eq_ignore_ascii_case(host))
+            {
+                return None;
+            }
+        }
+
+        match self.privacy.url_capture {
+            UrlCapture::Full => Some(url),
+            UrlCapture::HostOnly => host,
+            UrlCapture::None => None,
+        }
+    }
+}
+
+/// Extracts the host from a URL without pulling in a full URL-parsing
+/// dependency: strips the scheme, then takes everything up to the next
+/// `/`, `?`, or `#`.
+fn extract_host(url: &str) -> Option<String> {
+    let without_scheme = url.split("://").nth(1).unwrap_or(url);
+    let host = without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or(without_scheme);
+
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_string())
+    }
+}
+
+/// Matches `value` against a glob pattern supporting `*` as a
+/// multi-character wildcard. Good enough for bundle-id and host patterns
+/// without pulling in a general-purpose glob crate.
+fn glob_match(pattern: &str, value: &str) -> bool {
+    fn match_here(pattern: &[u8], value: &[u8]) -> bool {
+        match pattern.first() {
+            None => value.is_empty(),
+            Some(b'*') => {
+                (0..=value.len()).any(|i| match_here(&pattern[1..], &value[i..]))
+            }
+            Some(&p) => value.first().is_some_and(|&v| v == p) && match_here(&pattern[1..], &value[1..]),
+        }
+    }
+
+    match_here(pattern.as_bytes(), value.as_bytes())
+}
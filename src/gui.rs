@@ -0,0 +1,250 @@
+use crate::cdp::CdpClient;
+use crate::config::Config;
+use crate::state::SharedAppState;
+use crate::store::UsageStats;
+use crate::tracker::{self, TrackerEvent};
+use eframe::egui;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Spawns the tracking loop on a background thread and blocks the calling
+/// thread running the egui event loop until the window is closed or the
+/// user hits Ctrl+C.
+pub fn run(
+    usage_stats: Arc<UsageStats>,
+    cdp_client: Option<CdpClient>,
+    config: Config,
+    running: Arc<AtomicBool>,
+    state: SharedAppState,
+) {
+    let (tx, rx) = mpsc::channel();
+
+    let tracker_stats = usage_stats.clone();
+    let tracker_running = running.clone();
+    let tracker_state = state.clone();
+    let tracker_handle = thread::spawn(move || {
+        tracker::run_loop(&tracker_stats, cdp_client, &config, tracker_running, tracker_state, Some(tx), true);
+    });
+
+    let app = DashboardApp {
+        state,
+        events: rx,
+        log: Vec::new(),
+        running: running.clone(),
+        app_sort: AppSort::default(),
+    };
+
+    let native_options = eframe::NativeOptions::default();
+    if let Err(e) = eframe::run_native(
+        "Activity Tracker",
+        native_options,
+        Box::new(|_cc| Box::new(app)),
+    ) {
+        eprintln!("Failed to launch dashboard: {}", e);
+    }
+
+    // `eframe::run_native` can return because the window was closed
+    // natively (menu bar / title bar), which only flips `running` from
+    // inside `DashboardApp::on_exit`. Make sure the tracker thread has
+    // actually observed that and flushed its final session before we
+    // return and race ahead into CSV export / the summary.
+    running.store(false, Ordering::SeqCst);
+    let _ = tracker_handle.join();
+}
+
+/// Column the "By Application" table is currently sorted on, and in which
+/// direction. Clicking a header toggles direction if it's already selected,
+/// or switches to that column (descending) otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AppSortColumn {
+    Name,
+    Category,
+    Time,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AppSort {
+    column: AppSortColumn,
+    ascending: bool,
+}
+
+impl Default for AppSort {
+    fn default() -> Self {
+        Self { column: AppSortColumn::Time, ascending: false }
+    }
+}
+
+impl AppSort {
+    /// Updates the sort state for a header click.
+    fn click(&mut self, column: AppSortColumn) {
+        if self.column == column {
+            self.ascending = !self.ascending;
+        } else {
+            self.column = column;
+            self.ascending = false;
+        }
+    }
+
+    fn arrow(&self, column: AppSortColumn) -> &'static str {
+        if self.column != column {
+            return "";
+        }
+        if self.ascending {
+            " ▲"
+        } else {
+            " ▼"
+        }
+    }
+}
+
+struct DashboardApp {
+    state: SharedAppState,
+    events: mpsc::Receiver<TrackerEvent>,
+    log: Vec<String>,
+    running: Arc<AtomicBool>,
+    app_sort: AppSort,
+}
+
+impl DashboardApp {
+    fn drain_events(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                TrackerEvent::Switched { from, to } => {
+                    if let Some(entity) = from {
+                        self.log.push(format!("Left {}", entity.name));
+                    }
+                    if let Some(entity) = &to {
+                        self.log.push(format!("Now tracking {}", entity.name));
+                    }
+                }
+            }
+        }
+
+        // Keep the log from growing unbounded over a long-running session.
+        if self.log.len() > 200 {
+            let overflow = self.log.len() - 200;
+            self.log.drain(0..overflow);
+        }
+    }
+}
+
+impl eframe::App for DashboardApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.drain_events();
+
+        let snapshot = self.state.read().ok().map(|state| {
+            let mut categories: Vec<_> = state.category_stats.clone().into_iter().collect();
+            categories.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+
+            let apps: Vec<_> = state.app_stats.clone().into_iter().collect();
+
+            (state.current_entity.clone(), categories, apps, state.total_duration)
+        });
+
+        // Each panel is its own freely-draggable, freely-stackable window
+        // rather than a fixed side/central panel, so the layout can be
+        // rearranged to taste instead of being pinned in code.
+        egui::Window::new("Current App")
+            .default_pos((12.0, 12.0))
+            .show(ctx, |ui| match snapshot.as_ref().and_then(|(current, ..)| current.clone()) {
+                Some(entity) => {
+                    ui.label(format!("{} ({})", entity.name, entity.bundle_id));
+                    if let Some(url) = entity.url {
+                        ui.label(url);
+                    }
+                }
+                None => {
+                    ui.label("Idle");
+                }
+            });
+
+        egui::Window::new("Activity Log")
+            .default_pos((12.0, 140.0))
+            .default_size((320.0, 240.0))
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for line in self.log.iter().rev() {
+                        ui.label(line);
+                    }
+                });
+            });
+
+        let Some((_, categories, apps, total)) = snapshot else {
+            egui::Window::new("By Category").show(ctx, |ui| {
+                ui.label("Waiting for data...");
+            });
+            ctx.request_repaint_after(Duration::from_millis(500));
+            return;
+        };
+
+        egui::Window::new("By Category")
+            .default_pos((360.0, 12.0))
+            .show(ctx, |ui| {
+                for (category, duration) in &categories {
+                    let fraction = if total.as_secs_f64() > 0.0 {
+                        (duration.as_secs_f64() / total.as_secs_f64()) as f32
+                    } else {
+                        0.0
+                    };
+                    ui.label(format!("{}: {:.1?}", category, duration));
+                    ui.add(egui::ProgressBar::new(fraction));
+                }
+            });
+
+        let mut sorted_apps = apps;
+        sorted_apps.sort_by(|(entity_a, duration_a), (entity_b, duration_b)| {
+            let ordering = match self.app_sort.column {
+                AppSortColumn::Name => entity_a.name.cmp(&entity_b.name),
+                AppSortColumn::Category => entity_a.category.cmp(&entity_b.category),
+                AppSortColumn::Time => duration_a.cmp(duration_b),
+            };
+            if self.app_sort.ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+
+        egui::Window::new("By Application")
+            .default_pos((360.0, 320.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("app_table").striped(true).show(ui, |ui| {
+                    if ui.button(format!("App{}", self.app_sort.arrow(AppSortColumn::Name))).clicked() {
+                        self.app_sort.click(AppSortColumn::Name);
+                    }
+                    if ui.button(format!("Category{}", self.app_sort.arrow(AppSortColumn::Category))).clicked() {
+                        self.app_sort.click(AppSortColumn::Category);
+                    }
+                    if ui.button(format!("Time{}", self.app_sort.arrow(AppSortColumn::Time))).clicked() {
+                        self.app_sort.click(AppSortColumn::Time);
+                    }
+                    ui.end_row();
+
+                    for (entity, duration) in &sorted_apps {
+                        ui.label(&entity.name);
+                        ui.label(entity.category.clone().unwrap_or_default());
+                        ui.label(format!("{:.1?}", duration));
+                        ui.end_row();
+                    }
+                });
+            });
+
+        ctx.request_repaint_after(Duration::from_millis(500));
+
+        if !self.running.load(Ordering::SeqCst) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+
+    /// Closing the window natively (title-bar close button, OS "Quit") only
+    /// reaches here, not the Ctrl+C handler — without this, the background
+    /// tracker thread would keep polling after the window disappears, and
+    /// `tracker::run_loop`'s final-session flush (gated on `running` going
+    /// false) would never run.
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
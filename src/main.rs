@@ -1,250 +1,26 @@
-use chrono::{DateTime, Local};
-use csv::Writer;
-use serde::{Deserialize, Serialize};
+mod cdp;
+mod config;
+#[cfg(feature = "gui")]
+mod gui;
+mod models;
+mod platform;
+mod state;
+mod store;
+mod sync;
+mod tracker;
+
+use cdp::CdpClient;
+use chrono::{Local, TimeZone};
+use config::Config;
+use models::ActiveEntity;
+use state::AppState;
 use std::collections::HashMap;
 use std::env;
-use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::thread;
-use std::time::{Duration, Instant};
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-struct ActiveEntity {
-    bundle_id: String,
-    name: String,
-    url: Option<String>,
-    category: Option<String>, // New field for categorizing apps
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct UsageSession {
-    #[serde(rename = "Start Time")]
-    start_time: DateTime<Local>,
-    #[serde(rename = "End Time")]
-    end_time: DateTime<Local>,
-    #[serde(rename = "Duration (seconds)")]
-    duration_seconds: f64,
-    #[serde(rename = "App Name")]
-    app_name: String,
-    #[serde(rename = "Bundle ID")]
-    bundle_id: String,
-    #[serde(rename = "Category")]
-    category: String,
-    #[serde(rename = "URL")]
-    url: String,
-}
-
-impl UsageSession {
-    fn from_entity(
-        entity: &ActiveEntity,
-        start: DateTime<Local>,
-        end: DateTime<Local>,
-        duration: Duration,
-    ) -> Self {
-        Self {
-            start_time: start,
-            end_time: end,
-            duration_seconds: duration.as_secs_f64(),
-            app_name: entity.name.clone(),
-            bundle_id: entity.bundle_id.clone(),
-            category: entity
-                .category
-                .clone()
-                .unwrap_or_else(|| "Uncategorized".to_string()),
-            url: entity.url.clone().unwrap_or_else(|| "".to_string()),
-        }
-    }
-}
-
-#[derive(Debug)]
-struct UsageStats {
-    sessions: Vec<UsageSession>,
-    total_duration: Duration,
-    last_updated: DateTime<Local>,
-}
-
-impl UsageStats {
-    fn new() -> Self {
-        Self {
-            sessions: Vec::new(),
-            total_duration: Duration::ZERO,
-            last_updated: Local::now(),
-        }
-    }
-
-    fn add_session(
-        &mut self,
-        entity: &ActiveEntity,
-        start_time: DateTime<Local>,
-        end_time: DateTime<Local>,
-        duration: Duration,
-    ) {
-        self.sessions.push(UsageSession::from_entity(
-            entity, start_time, end_time, duration,
-        ));
-        self.total_duration += duration;
-        self.last_updated = Local::now();
-    }
-
-    fn save_to_file(&self, path: &PathBuf) -> Result<(), String> {
-        let mut wtr =
-            Writer::from_path(path).map_err(|e| format!("Failed to create CSV file: {}", e))?;
-
-        // Write header
-        wtr.write_record(&[
-            "Start Time",
-            "End Time",
-            "Duration (seconds)",
-            "App Name",
-            "Bundle ID",
-            "Category",
-            "URL",
-        ])
-        .map_err(|e| format!("Failed to write CSV header: {}", e))?;
-
-        // Write each session
-        for session in &self.sessions {
-            wtr.serialize(session)
-                .map_err(|e| format!("Failed to write session to CSV: {}", e))?;
-        }
-
-        wtr.flush()
-            .map_err(|e| format!("Failed to flush CSV file: {}", e))?;
-
-        Ok(())
-    }
-
-    fn load_from_file(path: &PathBuf) -> Result<Self, String> {
-        if !path.exists() {
-            return Ok(Self::new());
-        }
-
-        let mut rdr =
-            csv::Reader::from_path(path).map_err(|e| format!("Failed to read CSV file: {}", e))?;
-
-        let mut stats = Self::new();
-        let mut total_duration = Duration::ZERO;
-
-        for result in rdr.deserialize() {
-            let session: UsageSession =
-                result.map_err(|e| format!("Failed to parse CSV record: {}", e))?;
-
-            total_duration += Duration::from_secs_f64(session.duration_seconds);
-            stats.sessions.push(session);
-        }
-
-        stats.total_duration = total_duration;
-        stats.last_updated = Local::now();
-        Ok(stats)
-    }
-}
-
-fn run_osascript(script: &str) -> Result<String, String> {
-    let output = Command::new("osascript").arg("-e").arg(script).output();
-
-    match output {
-        Ok(out) => {
-            if out.status.success() {
-                let stdout = String::from_utf8(out.stdout)
-                    .unwrap_or_default()
-                    .trim()
-                    .to_string();
-                if stdout == "missing value" || stdout.is_empty() {
-                    Err("AppleScript returned missing value or empty string".to_string())
-                } else {
-                    Ok(stdout)
-                }
-            } else {
-                let stderr = String::from_utf8(out.stderr).unwrap_or_default();
-                Err(format!("osascript error: {}", stderr))
-            }
-        }
-        Err(e) => Err(format!("Failed to execute osascript: {}", e)),
-    }
-}
-
-fn get_active_app_info() -> Option<(String, String)> {
-    // (bundle_id, name)
-    let script_bundle_id = r#"tell application "System Events" to get bundle identifier of first process whose frontmost is true"#;
-    let script_name =
-        r#"tell application "System Events" to get name of first process whose frontmost is true"#;
-
-    match (run_osascript(script_bundle_id), run_osascript(script_name)) {
-        (Ok(bundle_id), Ok(name)) => Some((bundle_id, name)),
-        (Err(e_bundle), _) => {
-            // Only log errors if they're not empty and not during shutdown
-            if !e_bundle.is_empty() && !e_bundle.contains("execution of AppleScript failed") {
-                eprintln!("Error getting bundle_id: {}", e_bundle);
-            }
-            None
-        }
-        (_, Err(e_name)) => {
-            if !e_name.is_empty() && !e_name.contains("execution of AppleScript failed") {
-                eprintln!("Error getting name: {}", e_name);
-            }
-            None
-        }
-    }
-}
-
-fn get_browser_tab_url(bundle_id: &str) -> Option<String> {
-    let script = match bundle_id {
-        "company.thebrowser.dia"
-        | "com.google.Chrome"
-        | "com.google.Chrome.canary"
-        | "com.brave.Browser" => {
-            r#"tell application id "com.google.Chrome" to get URL of active tab of front window"#
-        }
-        "com.apple.Safari" => {
-            r#"tell application "Safari" to get URL of current tab of front window"#
-        }
-        "com.microsoft.edgemac" => {
-            // Added Edge explicitly
-            r#"tell application id "com.microsoft.edgemac" to get URL of active tab of front window"#
-        }
-        _ => return None,
-    };
-
-    let mut result = run_osascript(script);
-    if result.is_err() && bundle_id == "com.brave.Browser" {
-        // Specific fallback for Brave if generic Chrome ID fails
-        let brave_script =
-            r#"tell application id "com.brave.Browser" to get URL of active tab of front window"#;
-        result = run_osascript(brave_script);
-    }
-
-    match result {
-        Ok(url) => Some(url),
-        Err(e) => {
-            if !e.contains("missing value")
-                && !e.contains("Can't get window 1")
-                && !e.contains("Can't get current tab of window 1")
-            {
-                eprintln!("Error getting URL for {}: {}", bundle_id, e);
-            }
-            None
-        }
-    }
-}
-
-fn get_app_category(bundle_id: &str, name: &str) -> Option<String> {
-    // Simple categorization logic - can be expanded
-    match bundle_id {
-        "com.google.Chrome"
-        | "com.google.Chrome.canary"
-        | "com.apple.Safari"
-        | "com.brave.Browser"
-        | "com.microsoft.edgemac" => Some("Browser".to_string()),
-        "com.apple.Terminal" | "com.apple.iTerm2" => Some("Terminal".to_string()),
-        "com.apple.mail" | "com.microsoft.Outlook" => Some("Email".to_string()),
-        "com.apple.Slack" | "com.microsoft.Teams" => Some("Communication".to_string()),
-        "com.apple.Notes" | "com.apple.TextEdit" => Some("Productivity".to_string()),
-        _ => None,
-    }
-}
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use store::UsageStats;
 
 fn get_desktop_path() -> Result<PathBuf, String> {
     let home = env::var("HOME").map_err(|_| "Could not find HOME directory".to_string())?;
@@ -258,6 +34,14 @@ fn get_desktop_path() -> Result<PathBuf, String> {
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("sync") {
+        return run_sync(args.get(2));
+    }
+    if args.get(1).map(String::as_str) == Some("range") {
+        return run_range_summary(&args[2..]);
+    }
+
     let desktop_path = match get_desktop_path() {
         Ok(path) => path,
         Err(e) => {
@@ -266,16 +50,14 @@ fn main() {
         }
     };
 
-    let stats_file = desktop_path.join("usage_stats.csv");
-    let mut usage_stats = UsageStats::load_from_file(&stats_file).unwrap_or_else(|e| {
-        eprintln!("Warning: Could not load existing stats: {}", e);
-        UsageStats::new()
-    });
-
-    let mut current_entity: Option<ActiveEntity> = None;
-    let mut last_check_time = Instant::now();
-    let mut session_start_time = Local::now();
-    let mut is_shutting_down = false;
+    let db_file = desktop_path.join("usage_stats.db");
+    let usage_stats = match UsageStats::open(&db_file) {
+        Ok(stats) => Arc::new(stats),
+        Err(e) => {
+            eprintln!("Error: Could not open session database: {}", e);
+            return;
+        }
+    };
 
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
@@ -286,98 +68,93 @@ fn main() {
     })
     .expect("Error setting Ctrl-C handler");
 
-    println!("Starting app tracker... Press Ctrl+C to stop and show summary.");
+    let cdp_client = cdp::debugging_port_from_args().map(CdpClient::new);
+    if cdp_client.is_some() {
+        println!("Chrome DevTools Protocol capture enabled.");
+    }
 
-    while running.load(Ordering::SeqCst) {
-        let new_entity_info = get_active_app_info();
-        let mut new_active_entity: Option<ActiveEntity> = None;
-
-        if let Some((bundle_id, name)) = new_entity_info {
-            let mut url: Option<String> = None;
-            match bundle_id.as_str() {
-                "com.google.Chrome"
-                | "com.google.Chrome.canary"
-                | "com.apple.Safari"
-                | "com.brave.Browser"
-                | "com.microsoft.edgemac" => {
-                    url = get_browser_tab_url(&bundle_id);
-                }
-                _ => {}
-            }
+    let config = Config::load_or_default();
 
-            let category = get_app_category(&bundle_id, &name);
-            new_active_entity = Some(ActiveEntity {
-                bundle_id,
-                name,
-                url,
-                category,
-            });
-        }
+    println!("Starting app tracker... Press Ctrl+C to stop and show summary.");
 
-        let loop_instant = Instant::now();
-        let elapsed_since_last_check = loop_instant.duration_since(last_check_time);
-
-        if current_entity != new_active_entity {
-            // Switched app/URL
-            if let Some(ref entity) = current_entity {
-                let session_end_time = Local::now();
-                usage_stats.add_session(
-                    entity,
-                    session_start_time,
-                    session_end_time,
-                    elapsed_since_last_check,
-                );
-                if !is_shutting_down {
-                    println!(
-                        "Switched from: {:?} (spent: {:.2?})",
-                        entity, elapsed_since_last_check
-                    );
-                }
-            }
+    let state: state::SharedAppState = Arc::new(RwLock::new(AppState::default()));
 
-            current_entity = new_active_entity;
-            session_start_time = Local::now();
+    #[cfg(feature = "gui")]
+    gui::run(usage_stats.clone(), cdp_client, config, running.clone(), state.clone());
 
-            if let Some(ref entity) = current_entity {
-                if !is_shutting_down {
-                    println!("Started tracking: {:?}", entity);
-                }
-            }
-        }
+    #[cfg(not(feature = "gui"))]
+    tracker::run_loop(&usage_stats, cdp_client, &config, running, state, None, false);
 
-        last_check_time = loop_instant;
-        thread::sleep(Duration::from_secs(2));
+    // Export the full history to CSV alongside the database for easy sharing.
+    let csv_file = desktop_path.join("usage_stats.csv");
+    if let Err(e) = usage_stats.export_csv(&csv_file) {
+        eprintln!("Warning: Failed to export CSV: {}", e);
     }
 
-    // Mark as shutting down to suppress unnecessary output
-    is_shutting_down = true;
-
-    // Save final session if there is one
-    if let Some(ref entity) = current_entity {
-        let final_exit_time = Local::now();
-        let duration_spent_on_last_entity =
-            final_exit_time.signed_duration_since(session_start_time);
-        usage_stats.add_session(
-            entity,
-            session_start_time,
-            final_exit_time,
-            Duration::from_secs(duration_spent_on_last_entity.num_seconds() as u64),
-        );
-    }
+    print_summary(&usage_stats, &db_file, &csv_file, device_filter_from_args().as_deref());
+}
+
+/// Reads `--device=<id>` from the process arguments, restricting the
+/// printed summary to sessions recorded by that device (see
+/// `sync::local_device_id`). Absent by default, so a bare run still
+/// summarizes every device's history.
+fn device_filter_from_args() -> Option<String> {
+    env::args().find_map(|arg| arg.strip_prefix("--device=").map(str::to_string))
+}
 
-    // Save stats to file
-    if let Err(e) = usage_stats.save_to_file(&stats_file) {
-        eprintln!("Warning: Failed to save usage stats: {}", e);
+/// Keeps only sessions recorded by `device_id`, if given.
+fn filter_by_device(sessions: Vec<models::UsageSession>, device_filter: Option<&str>) -> Vec<models::UsageSession> {
+    match device_filter {
+        Some(device_id) => sessions.into_iter().filter(|s| s.device_id == device_id).collect(),
+        None => sessions,
     }
+}
 
-    // Print summary
+fn print_summary(usage_stats: &UsageStats, db_file: &Path, csv_file: &Path, device_filter: Option<&str>) {
     println!("\n=== Usage Summary ===");
 
+    let sessions = usage_stats.all_sessions().unwrap_or_else(|e| {
+        eprintln!("Warning: Could not read back sessions: {}", e);
+        Vec::new()
+    });
+    let sessions = filter_by_device(sessions, device_filter);
+    let total_duration = match device_filter {
+        // Filtering drops rows the cheap SQL-side SUM would still count,
+        // so total it up from what's left over instead.
+        Some(_) => sessions.iter().map(|s| Duration::from_secs_f64(s.duration_seconds)).sum(),
+        None => usage_stats.total_duration().unwrap_or(Duration::ZERO),
+    };
+
+    print_sessions_report(&sessions, total_duration);
+    println!("Sessions stored in: {}", db_file.display());
+    println!("CSV export saved to: {}", csv_file.display());
+}
+
+/// Prints the category/application/device/idle breakdown shared by the
+/// full-history summary and `activity_tracker range`.
+fn print_sessions_report(sessions: &[models::UsageSession], total_duration: Duration) {
+    // Group by device, so a multi-machine history can be told apart even
+    // when it isn't filtered down to one.
+    let mut device_stats: HashMap<String, Duration> = HashMap::new();
+    for session in sessions {
+        *device_stats.entry(session.device_id.clone()).or_insert(Duration::ZERO) +=
+            Duration::from_secs_f64(session.duration_seconds);
+    }
+
+    println!("\nBy Device:");
+    let mut sorted_devices: Vec<_> = device_stats.into_iter().collect();
+    sorted_devices.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+
+    for (device_id, duration) in sorted_devices {
+        let percentage = (duration.as_secs_f64() / total_duration.as_secs_f64()) * 100.0;
+        println!("{}: {:.2?} ({:.1}%)", device_id, duration, percentage);
+    }
+
     // Group by category
     let mut category_stats: HashMap<String, Duration> = HashMap::new();
     let mut app_stats: HashMap<ActiveEntity, Duration> = HashMap::new();
 
-    for session in &usage_stats.sessions {
+    for session in sessions {
         let category = session.category.clone();
         *category_stats
             .entry(category.clone())
@@ -387,6 +164,7 @@ fn main() {
                 bundle_id: session.bundle_id.clone(),
                 name: session.app_name.clone(),
                 url: None,
+                title: None,
                 category: Some(category),
             })
             .or_insert(Duration::ZERO) += Duration::from_secs_f64(session.duration_seconds);
@@ -394,21 +172,19 @@ fn main() {
 
     println!("\nBy Category:");
     let mut sorted_categories: Vec<_> = category_stats.into_iter().collect();
-    sorted_categories.sort_by(|a, b| b.1.cmp(&a.1));
+    sorted_categories.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
 
     for (category, duration) in sorted_categories {
-        let percentage =
-            (duration.as_secs_f64() / usage_stats.total_duration.as_secs_f64()) * 100.0;
+        let percentage = (duration.as_secs_f64() / total_duration.as_secs_f64()) * 100.0;
         println!("{}: {:.2?} ({:.1}%)", category, duration, percentage);
     }
 
     println!("\nBy Application:");
     let mut sorted_apps: Vec<_> = app_stats.into_iter().collect();
-    sorted_apps.sort_by(|a, b| b.1.cmp(&a.1));
+    sorted_apps.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
 
     for (entity, duration) in sorted_apps {
-        let percentage =
-            (duration.as_secs_f64() / usage_stats.total_duration.as_secs_f64()) * 100.0;
+        let percentage = (duration.as_secs_f64() / total_duration.as_secs_f64()) * 100.0;
         println!("\nApp: {} ({})", entity.name, entity.bundle_id);
         if let Some(url) = entity.url {
             println!("  URL: {}", url);
@@ -419,6 +195,118 @@ fn main() {
         println!("  Total Time: {:.2?} ({:.1}%)", duration, percentage);
     }
 
-    println!("\nTotal tracked time: {:.2?}", usage_stats.total_duration);
-    println!("Stats saved to: {}", stats_file.display());
+    let idle_duration: Duration = sessions
+        .iter()
+        .filter(|s| s.bundle_id == "system.idle")
+        .map(|s| Duration::from_secs_f64(s.duration_seconds))
+        .sum();
+    let active_duration = total_duration.saturating_sub(idle_duration);
+
+    println!("\nActive time: {:.2?}", active_duration);
+    println!("Idle time: {:.2?}", idle_duration);
+    println!("Total tracked time: {:.2?}", total_duration);
+}
+
+/// Runs `activity_tracker range --from=<rfc3339> [--to=<rfc3339>]
+/// [--device=<id>]`: prints a breakdown for just that date range via
+/// `UsageStats::query_range`, so a long history doesn't have to be read
+/// into memory just to summarize one slice of it.
+fn run_range_summary(args: &[String]) {
+    let desktop_path = match get_desktop_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error: {}. Using current directory instead.", e);
+            PathBuf::from(".")
+        }
+    };
+
+    let usage_stats = match UsageStats::open(&desktop_path.join("usage_stats.db")) {
+        Ok(stats) => stats,
+        Err(e) => {
+            eprintln!("Error: Could not open session database: {}", e);
+            return;
+        }
+    };
+
+    let arg_value = |flag: &str| args.iter().find_map(|a| a.strip_prefix(flag));
+    let from = arg_value("--from=")
+        .and_then(parse_rfc3339_arg)
+        .unwrap_or_else(|| Local.timestamp_opt(0, 0).unwrap());
+    let to = arg_value("--to=").and_then(parse_rfc3339_arg).unwrap_or_else(Local::now);
+    let device_filter = arg_value("--device=");
+
+    let sessions = match usage_stats.query_range(from, to) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            eprintln!("Error: Failed to query sessions: {}", e);
+            return;
+        }
+    };
+    let sessions = filter_by_device(sessions, device_filter);
+    let total_duration = sessions.iter().map(|s| Duration::from_secs_f64(s.duration_seconds)).sum();
+
+    println!("\n=== Usage Summary ({} to {}) ===", from.to_rfc3339(), to.to_rfc3339());
+    print_sessions_report(&sessions, total_duration);
+}
+
+fn parse_rfc3339_arg(value: &str) -> Option<chrono::DateTime<Local>> {
+    match chrono::DateTime::parse_from_rfc3339(value) {
+        Ok(dt) => Some(dt.with_timezone(&Local)),
+        Err(e) => {
+            eprintln!("Warning: Ignoring unparsable date \"{}\": {}", value, e);
+            None
+        }
+    }
+}
+
+/// Runs `activity_tracker sync <directory-or-s3-url>`: pulls any sessions
+/// other devices have pushed since the last sync into the local store,
+/// then pushes everything recorded locally since then out to the target.
+fn run_sync(target_arg: Option<&String>) {
+    let Some(target_arg) = target_arg else {
+        eprintln!("Usage: activity_tracker sync <directory-or-s3-url>");
+        return;
+    };
+
+    let desktop_path = match get_desktop_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error: {}. Using current directory instead.", e);
+            PathBuf::from(".")
+        }
+    };
+
+    let usage_stats = match UsageStats::open(&desktop_path.join("usage_stats.db")) {
+        Ok(stats) => stats,
+        Err(e) => {
+            eprintln!("Error: Could not open session database: {}", e);
+            return;
+        }
+    };
+
+    let target = sync::target_from_arg(target_arg);
+    let since = sync::last_sync_time(target_arg);
+    let sync_started_at = Local::now();
+
+    match target.pull() {
+        Ok(records) => match usage_stats.apply_incoming(&records) {
+            Ok(applied) => println!("Applied {} incoming session(s) from {}.", applied, target_arg),
+            Err(e) => eprintln!("Error: Failed to apply incoming sessions: {}", e),
+        },
+        Err(e) => eprintln!("Error: Failed to pull sessions from {}: {}", target_arg, e),
+    }
+
+    match usage_stats.collect_outgoing(since) {
+        Ok(records) => {
+            let count = records.len();
+            match target.push(&records) {
+                Ok(()) => {
+                    println!("Pushed {} session(s) to {}.", count, target_arg);
+                    sync::save_last_sync_time(target_arg, sync_started_at);
+                }
+                Err(e) => eprintln!("Error: Failed to push sessions to {}: {}", target_arg, e),
+            }
+        }
+        Err(e) => eprintln!("Error: Failed to collect sessions to push: {}", e),
+    }
 }
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ActiveEntity {
+    pub bundle_id: String,
+    pub name: String,
+    pub url: Option<String>,
+    pub title: Option<String>, // Browser tab title, via CDP when available
+    pub category: Option<String>, // New field for categorizing apps
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageSession {
+    #[serde(rename = "Device ID")]
+    pub device_id: String,
+    #[serde(rename = "Start Time")]
+    pub start_time: DateTime<Local>,
+    #[serde(rename = "End Time")]
+    pub end_time: DateTime<Local>,
+    #[serde(rename = "Duration (seconds)")]
+    pub duration_seconds: f64,
+    #[serde(rename = "App Name")]
+    pub app_name: String,
+    #[serde(rename = "Bundle ID")]
+    pub bundle_id: String,
+    #[serde(rename = "Category")]
+    pub category: String,
+    #[serde(rename = "URL")]
+    pub url: String,
+    #[serde(rename = "Title")]
+    pub title: String,
+}
+
+impl UsageSession {
+    pub fn from_entity(
+        device_id: &str,
+        entity: &ActiveEntity,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            device_id: device_id.to_string(),
+            start_time: start,
+            end_time: end,
+            duration_seconds: duration.as_secs_f64(),
+            app_name: entity.name.clone(),
+            bundle_id: entity.bundle_id.clone(),
+            category: entity
+                .category
+                .clone()
+                .unwrap_or_else(|| "Uncategorized".to_string()),
+            url: entity.url.clone().unwrap_or_default(),
+            title: entity.title.clone().unwrap_or_default(),
+        }
+    }
+}
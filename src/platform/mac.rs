@@ -0,0 +1,129 @@
+use super::PlatformMonitor;
+use crate::models::ActiveEntity;
+use std::process::Command;
+
+/// macOS backend, driven by `osascript`/AppleScript via System Events.
+pub struct MacMonitor;
+
+impl PlatformMonitor for MacMonitor {
+    fn active_window(&self) -> Option<ActiveEntity> {
+        let (bundle_id, name) = get_active_app_info()?;
+        Some(ActiveEntity {
+            bundle_id,
+            name,
+            url: None,
+            title: None,
+            category: None,
+        })
+    }
+
+    fn browser_url(&self, entity: &ActiveEntity) -> Option<String> {
+        get_browser_tab_url(&entity.bundle_id)
+    }
+
+    fn idle_seconds(&self) -> Option<f64> {
+        const K_CG_EVENT_SOURCE_STATE_COMBINED_SESSION_STATE: i32 = 0;
+        const K_CG_ANY_INPUT_EVENT_TYPE: u32 = !0;
+
+        #[link(name = "ApplicationServices", kind = "framework")]
+        extern "C" {
+            fn CGEventSourceSecondsSinceLastEventType(state_id: i32, event_type: u32) -> f64;
+        }
+
+        let seconds = unsafe {
+            CGEventSourceSecondsSinceLastEventType(
+                K_CG_EVENT_SOURCE_STATE_COMBINED_SESSION_STATE,
+                K_CG_ANY_INPUT_EVENT_TYPE,
+            )
+        };
+        Some(seconds)
+    }
+}
+
+fn run_osascript(script: &str) -> Result<String, String> {
+    let output = Command::new("osascript").arg("-e").arg(script).output();
+
+    match output {
+        Ok(out) => {
+            if out.status.success() {
+                let stdout = String::from_utf8(out.stdout)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                if stdout == "missing value" || stdout.is_empty() {
+                    Err("AppleScript returned missing value or empty string".to_string())
+                } else {
+                    Ok(stdout)
+                }
+            } else {
+                let stderr = String::from_utf8(out.stderr).unwrap_or_default();
+                Err(format!("osascript error: {}", stderr))
+            }
+        }
+        Err(e) => Err(format!("Failed to execute osascript: {}", e)),
+    }
+}
+
+fn get_active_app_info() -> Option<(String, String)> {
+    // (bundle_id, name)
+    let script_bundle_id = r#"tell application "System Events" to get bundle identifier of first process whose frontmost is true"#;
+    let script_name =
+        r#"tell application "System Events" to get name of first process whose frontmost is true"#;
+
+    match (run_osascript(script_bundle_id), run_osascript(script_name)) {
+        (Ok(bundle_id), Ok(name)) => Some((bundle_id, name)),
+        (Err(e_bundle), _) => {
+            // Only log errors if they're not empty and not during shutdown
+            if !e_bundle.is_empty() && !e_bundle.contains("execution of AppleScript failed") {
+                eprintln!("Error getting bundle_id: {}", e_bundle);
+            }
+            None
+        }
+        (_, Err(e_name)) => {
+            if !e_name.is_empty() && !e_name.contains("execution of AppleScript failed") {
+                eprintln!("Error getting name: {}", e_name);
+            }
+            None
+        }
+    }
+}
+
+fn get_browser_tab_url(bundle_id: &str) -> Option<String> {
+    let script = match bundle_id {
+        "company.thebrowser.dia"
+        | "com.google.Chrome"
+        | "com.google.Chrome.canary"
+        | "com.brave.Browser" => {
+            r#"tell application id "com.google.Chrome" to get URL of active tab of front window"#
+        }
+        "com.apple.Safari" => {
+            r#"tell application "Safari" to get URL of current tab of front window"#
+        }
+        "com.microsoft.edgemac" => {
+            // Added Edge explicitly
+            r#"tell application id "com.microsoft.edgemac" to get URL of active tab of front window"#
+        }
+        _ => return None,
+    };
+
+    let mut result = run_osascript(script);
+    if result.is_err() && bundle_id == "com.brave.Browser" {
+        // Specific fallback for Brave if generic Chrome ID fails
+        let brave_script =
+            r#"tell application id "com.brave.Browser" to get URL of active tab of front window"#;
+        result = run_osascript(brave_script);
+    }
+
+    match result {
+        Ok(url) => Some(url),
+        Err(e) => {
+            if !e.contains("missing value")
+                && !e.contains("Can't get window 1")
+                && !e.contains("Can't get current tab of window 1")
+            {
+                eprintln!("Error getting URL for {}: {}", bundle_id, e);
+            }
+            None
+        }
+    }
+}
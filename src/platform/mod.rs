@@ -0,0 +1,49 @@
+use crate::models::ActiveEntity;
+
+#[cfg(target_os = "macos")]
+mod mac;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "windows")]
+mod windows;
+
+/// Captures the foreground application and, where the OS exposes one, its
+/// active browser tab URL. `tracker` only ever talks to this trait, so
+/// adding a new OS means adding an implementation here rather than
+/// touching the tracking loop.
+pub trait PlatformMonitor {
+    /// Returns the identifier and display name of the currently focused
+    /// application, if one can be determined.
+    fn active_window(&self) -> Option<ActiveEntity>;
+
+    /// Returns the URL of the active browser tab for `entity`, if `entity`
+    /// is a recognized browser and this platform can read it natively.
+    fn browser_url(&self, entity: &ActiveEntity) -> Option<String>;
+
+    /// Returns how long the user has been away from keyboard/mouse, in
+    /// seconds, if the platform exposes an idle timer. `None` means idle
+    /// time can't be determined here, which callers should treat as "not
+    /// idle" rather than an error.
+    fn idle_seconds(&self) -> Option<f64>;
+}
+
+/// Selects the `PlatformMonitor` implementation for the OS this binary
+/// was built for.
+pub fn current() -> Box<dyn PlatformMonitor + Send + Sync> {
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(mac::MacMonitor)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(linux::LinuxMonitor)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(windows::WindowsMonitor)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        compile_error!("activity_tracker has no PlatformMonitor for this target OS");
+    }
+}
@@ -0,0 +1,86 @@
+use super::PlatformMonitor;
+use crate::models::ActiveEntity;
+use windows::Win32::Foundation::CloseHandle;
+use windows::Win32::System::SystemInformation::GetTickCount;
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+};
+
+/// Windows backend, reading the foreground window via
+/// `GetForegroundWindow`/`GetWindowThreadProcessId` and resolving its
+/// owning process's executable path as a stand-in for a bundle id.
+pub struct WindowsMonitor;
+
+impl PlatformMonitor for WindowsMonitor {
+    fn active_window(&self) -> Option<ActiveEntity> {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0 == 0 {
+                return None;
+            }
+
+            let mut title_buf = [0u16; 512];
+            let len = GetWindowTextW(hwnd, &mut title_buf);
+            let name = String::from_utf16_lossy(&title_buf[..len.max(0) as usize]);
+
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+
+            let bundle_id = process_image_name(pid).unwrap_or_else(|| "unknown.exe".to_string());
+
+            Some(ActiveEntity {
+                bundle_id,
+                name,
+                url: None,
+                title: None,
+                category: None,
+            })
+        }
+    }
+
+    fn browser_url(&self, _entity: &ActiveEntity) -> Option<String> {
+        // Win32 has no generic "active tab URL" API. `tracker` only looks
+        // up tab info (CDP included) for the macOS browser bundle ids,
+        // which executable paths do not match, so no URL is captured on
+        // this platform.
+        None
+    }
+
+    fn idle_seconds(&self) -> Option<f64> {
+        unsafe {
+            let mut info = LASTINPUTINFO {
+                cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+                dwTime: 0,
+            };
+            if !GetLastInputInfo(&mut info).as_bool() {
+                return None;
+            }
+
+            let idle_ticks = GetTickCount().saturating_sub(info.dwTime);
+            Some(idle_ticks as f64 / 1000.0)
+        }
+    }
+}
+
+unsafe fn process_image_name(pid: u32) -> Option<String> {
+    let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+    let mut buf = [0u16; 512];
+    let mut size = buf.len() as u32;
+    let result = QueryFullProcessImageNameW(
+        handle,
+        PROCESS_NAME_WIN32,
+        windows::core::PWSTR(buf.as_mut_ptr()),
+        &mut size,
+    );
+    let _ = CloseHandle(handle);
+
+    if result.as_bool() {
+        Some(String::from_utf16_lossy(&buf[..size as usize]))
+    } else {
+        None
+    }
+}
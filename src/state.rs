@@ -0,0 +1,49 @@
+use crate::models::ActiveEntity;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Live view of the tracking session, kept up to date by the tracker
+/// thread and, when the `gui` feature is enabled, read by the repaint
+/// loop. The printed summary is built from the store instead.
+#[derive(Debug, Default)]
+pub struct AppState {
+    pub current_entity: Option<ActiveEntity>,
+    pub category_stats: HashMap<String, Duration>,
+    pub app_stats: HashMap<ActiveEntity, Duration>,
+    pub total_duration: Duration,
+}
+
+impl AppState {
+    pub fn set_current(&mut self, entity: Option<ActiveEntity>) {
+        self.current_entity = entity;
+    }
+
+    /// Folds a just-completed session into the running category/app totals.
+    pub fn record_session(&mut self, entity: &ActiveEntity, duration: Duration) {
+        let category = entity
+            .category
+            .clone()
+            .unwrap_or_else(|| "Uncategorized".to_string());
+
+        *self
+            .category_stats
+            .entry(category.clone())
+            .or_insert(Duration::ZERO) += duration;
+
+        *self
+            .app_stats
+            .entry(ActiveEntity {
+                bundle_id: entity.bundle_id.clone(),
+                name: entity.name.clone(),
+                url: None,
+                title: None,
+                category: Some(category),
+            })
+            .or_insert(Duration::ZERO) += duration;
+
+        self.total_duration += duration;
+    }
+}
+
+pub type SharedAppState = Arc<RwLock<AppState>>;
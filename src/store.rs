@@ -0,0 +1,373 @@
+use crate::models::{ActiveEntity, UsageSession};
+use crate::sync::{self, SyncRecord};
+use chrono::{DateTime, Local, TimeZone};
+use csv::Writer;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// SQLite-backed repository for recorded usage sessions.
+///
+/// Sessions are inserted one at a time as they happen, so a crash only
+/// loses whatever was in flight rather than the whole run. The connection
+/// is kept behind a `Mutex` so the store can later be shared across the
+/// tracking loop and a reporting/UI thread.
+pub struct UsageStats {
+    conn: Mutex<Connection>,
+    /// This machine's stable sync identity; stamped onto every session
+    /// this process records. See [`sync::local_device_id`].
+    device_id: String,
+}
+
+impl UsageStats {
+    /// Opens (and creates, if necessary) the sessions database at `path`.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn =
+            Connection::open(path).map_err(|e| format!("Failed to open session database: {}", e))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+                uuid                TEXT NOT NULL UNIQUE,
+                device_id           TEXT NOT NULL DEFAULT '',
+                updated_at          TEXT NOT NULL DEFAULT '',
+                start_time          TEXT NOT NULL,
+                end_time            TEXT NOT NULL,
+                duration_seconds    REAL NOT NULL,
+                app_name            TEXT NOT NULL,
+                bundle_id           TEXT NOT NULL,
+                category            TEXT NOT NULL,
+                url                 TEXT NOT NULL,
+                title               TEXT NOT NULL DEFAULT ''
+            );
+            CREATE INDEX IF NOT EXISTS idx_sessions_bundle_id ON sessions(bundle_id);
+            CREATE INDEX IF NOT EXISTS idx_sessions_start_time ON sessions(start_time);
+            CREATE INDEX IF NOT EXISTS idx_sessions_device_id ON sessions(device_id);",
+        )
+        .map_err(|e| format!("Failed to initialize session database: {}", e))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            device_id: sync::local_device_id(),
+        })
+    }
+
+    /// Records a single completed session immediately.
+    pub fn add_session(
+        &self,
+        entity: &ActiveEntity,
+        start_time: DateTime<Local>,
+        end_time: DateTime<Local>,
+        duration: Duration,
+    ) -> Result<(), String> {
+        let session = UsageSession::from_entity(&self.device_id, entity, start_time, end_time, duration);
+        let conn = self.conn.lock().map_err(|_| "Session database lock poisoned".to_string())?;
+
+        conn.execute(
+            "INSERT INTO sessions (uuid, device_id, updated_at, start_time, end_time, duration_seconds, app_name, bundle_id, category, url, title)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                Uuid::new_v4().to_string(),
+                self.device_id,
+                Local::now().to_rfc3339(),
+                session.start_time.to_rfc3339(),
+                session.end_time.to_rfc3339(),
+                session.duration_seconds,
+                session.app_name,
+                session.bundle_id,
+                session.category,
+                session.url,
+                session.title,
+            ],
+        )
+        .map_err(|e| format!("Failed to insert session: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Merges sessions pulled from another device into the local store.
+    /// A record overwrites the local row with the same `uuid` only if its
+    /// `updated_at` is newer (last-writer-wins), and is inserted outright
+    /// if the `uuid` isn't known locally yet. Returns how many rows were
+    /// written.
+    pub fn apply_incoming(&self, records: &[SyncRecord]) -> Result<usize, String> {
+        let conn = self.conn.lock().map_err(|_| "Session database lock poisoned".to_string())?;
+        let mut applied = 0;
+
+        for record in records {
+            let existing_updated_at: Option<String> = conn
+                .query_row(
+                    "SELECT updated_at FROM sessions WHERE uuid = ?1",
+                    params![record.id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| format!("Failed to check existing session: {}", e))?;
+
+            let is_newer = match existing_updated_at {
+                Some(existing) => {
+                    let existing = parse_rfc3339(0, &existing)
+                        .map_err(|e| format!("Corrupt updated_at for session {}: {}", record.id, e))?;
+                    existing < record.updated_at
+                }
+                None => true,
+            };
+
+            if !is_newer {
+                continue;
+            }
+
+            conn.execute(
+                "INSERT INTO sessions (uuid, device_id, updated_at, start_time, end_time, duration_seconds, app_name, bundle_id, category, url, title)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                 ON CONFLICT(uuid) DO UPDATE SET
+                    device_id = excluded.device_id,
+                    updated_at = excluded.updated_at,
+                    start_time = excluded.start_time,
+                    end_time = excluded.end_time,
+                    duration_seconds = excluded.duration_seconds,
+                    app_name = excluded.app_name,
+                    bundle_id = excluded.bundle_id,
+                    category = excluded.category,
+                    url = excluded.url,
+                    title = excluded.title",
+                params![
+                    record.id,
+                    record.device_id,
+                    record.updated_at.to_rfc3339(),
+                    record.start_time.to_rfc3339(),
+                    record.end_time.to_rfc3339(),
+                    record.duration_seconds,
+                    record.app_name,
+                    record.bundle_id,
+                    record.category,
+                    record.url,
+                    record.title,
+                ],
+            )
+            .map_err(|e| format!("Failed to apply synced session: {}", e))?;
+
+            applied += 1;
+        }
+
+        Ok(applied)
+    }
+
+    /// Returns every session updated after `since`, for pushing to
+    /// another device.
+    pub fn collect_outgoing(&self, since: DateTime<Local>) -> Result<Vec<SyncRecord>, String> {
+        let conn = self.conn.lock().map_err(|_| "Session database lock poisoned".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT uuid, device_id, updated_at, start_time, end_time, duration_seconds, app_name, bundle_id, category, url, title
+                 FROM sessions
+                 WHERE updated_at > ?1
+                 ORDER BY updated_at ASC",
+            )
+            .map_err(|e| format!("Failed to prepare sync query: {}", e))?;
+
+        let rows = stmt
+            .query_map(params![since.to_rfc3339()], |row| {
+                let updated_at: String = row.get(2)?;
+                let start_time: String = row.get(3)?;
+                let end_time: String = row.get(4)?;
+                Ok(SyncRecord {
+                    id: row.get(0)?,
+                    device_id: row.get(1)?,
+                    updated_at: parse_rfc3339(2, &updated_at)?,
+                    start_time: parse_rfc3339(3, &start_time)?,
+                    end_time: parse_rfc3339(4, &end_time)?,
+                    duration_seconds: row.get(5)?,
+                    app_name: row.get(6)?,
+                    bundle_id: row.get(7)?,
+                    category: row.get(8)?,
+                    url: row.get(9)?,
+                    title: row.get(10)?,
+                })
+            })
+            .map_err(|e| format!("Failed to run sync query: {}", e))?;
+
+        Ok(skip_corrupt_rows(rows, "sync"))
+    }
+
+    /// Returns every session whose start time falls within `[from, to]`.
+    pub fn query_range(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+    ) -> Result<Vec<UsageSession>, String> {
+        let conn = self.conn.lock().map_err(|_| "Session database lock poisoned".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT device_id, start_time, end_time, duration_seconds, app_name, bundle_id, category, url, title
+                 FROM sessions
+                 WHERE start_time >= ?1 AND start_time <= ?2
+                 ORDER BY start_time ASC",
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let rows = stmt
+            .query_map(params![from.to_rfc3339(), to.to_rfc3339()], |row| {
+                let start_time: String = row.get(1)?;
+                let end_time: String = row.get(2)?;
+                Ok(UsageSession {
+                    device_id: row.get(0)?,
+                    start_time: parse_rfc3339(1, &start_time)?,
+                    end_time: parse_rfc3339(2, &end_time)?,
+                    duration_seconds: row.get(3)?,
+                    app_name: row.get(4)?,
+                    bundle_id: row.get(5)?,
+                    category: row.get(6)?,
+                    url: row.get(7)?,
+                    title: row.get(8)?,
+                })
+            })
+            .map_err(|e| format!("Failed to run query: {}", e))?;
+
+        Ok(skip_corrupt_rows(rows, "session"))
+    }
+
+    /// Returns every recorded session, oldest first.
+    pub fn all_sessions(&self) -> Result<Vec<UsageSession>, String> {
+        self.query_range(Local.timestamp_opt(0, 0).unwrap(), Local::now())
+    }
+
+    /// Sums `duration_seconds` across every recorded session.
+    pub fn total_duration(&self) -> Result<Duration, String> {
+        let conn = self.conn.lock().map_err(|_| "Session database lock poisoned".to_string())?;
+        let total: f64 = conn
+            .query_row(
+                "SELECT COALESCE(SUM(duration_seconds), 0.0) FROM sessions",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to sum session durations: {}", e))?;
+
+        Ok(Duration::from_secs_f64(total))
+    }
+
+    /// Exports the full session history to a CSV file, e.g. for sharing or
+    /// archival. The database remains the source of truth.
+    pub fn export_csv(&self, path: &Path) -> Result<(), String> {
+        let sessions = self.all_sessions()?;
+        let mut wtr =
+            Writer::from_path(path).map_err(|e| format!("Failed to create CSV file: {}", e))?;
+
+        wtr.write_record([
+            "Device ID",
+            "Start Time",
+            "End Time",
+            "Duration (seconds)",
+            "App Name",
+            "Bundle ID",
+            "Category",
+            "URL",
+            "Title",
+        ])
+        .map_err(|e| format!("Failed to write CSV header: {}", e))?;
+
+        for session in &sessions {
+            wtr.serialize(session)
+                .map_err(|e| format!("Failed to write session to CSV: {}", e))?;
+        }
+
+        wtr.flush()
+            .map_err(|e| format!("Failed to flush CSV file: {}", e))?;
+
+        Ok(())
+    }
+}
+
+/// Parses a timestamp stored in `column`, surfacing a conversion error
+/// instead of silently treating a corrupted or foreign-format row as if
+/// it had just happened.
+fn parse_rfc3339(column: usize, value: &str) -> rusqlite::Result<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Local))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(column, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+/// Drops and logs any row that failed to parse (e.g. a corrupt
+/// `parse_rfc3339` timestamp) instead of letting one bad row fail the
+/// whole query - a summary or sync push should still cover every other
+/// session rather than reporting nothing.
+fn skip_corrupt_rows<T>(rows: impl Iterator<Item = rusqlite::Result<T>>, context: &str) -> Vec<T> {
+    rows.filter_map(|row| match row {
+        Ok(value) => Some(value),
+        Err(e) => {
+            eprintln!("Warning: Skipping corrupt {} row: {}", context, e);
+            None
+        }
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_entity() -> ActiveEntity {
+        ActiveEntity {
+            bundle_id: "com.example.test".to_string(),
+            name: "Test App".to_string(),
+            url: None,
+            title: None,
+            category: Some("Testing".to_string()),
+        }
+    }
+
+    #[test]
+    fn collect_outgoing_round_trips_through_apply_incoming() {
+        let source = UsageStats::open(Path::new(":memory:")).unwrap();
+        let start = Local::now();
+        let end = start + chrono::Duration::seconds(30);
+        source
+            .add_session(&test_entity(), start, end, Duration::from_secs(30))
+            .unwrap();
+
+        let records = source.collect_outgoing(Local.timestamp_opt(0, 0).unwrap()).unwrap();
+        assert_eq!(records.len(), 1);
+
+        let destination = UsageStats::open(Path::new(":memory:")).unwrap();
+        let applied = destination.apply_incoming(&records).unwrap();
+        assert_eq!(applied, 1);
+
+        let sessions = destination.all_sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].bundle_id, "com.example.test");
+
+        // Re-applying the same (not-newer) record must not duplicate or
+        // overwrite the row.
+        let applied_again = destination.apply_incoming(&records).unwrap();
+        assert_eq!(applied_again, 0);
+        assert_eq!(destination.all_sessions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn apply_incoming_keeps_newer_record_on_conflict() {
+        let destination = UsageStats::open(Path::new(":memory:")).unwrap();
+        let start = Local::now();
+        destination
+            .add_session(&test_entity(), start, start, Duration::ZERO)
+            .unwrap();
+
+        let epoch = Local.timestamp_opt(0, 0).unwrap();
+        let stale = destination.collect_outgoing(epoch).unwrap().remove(0);
+
+        let mut newer = stale.clone();
+        newer.updated_at += chrono::Duration::seconds(60);
+        newer.category = "Updated".to_string();
+
+        assert_eq!(destination.apply_incoming(&[newer.clone()]).unwrap(), 1);
+        assert_eq!(destination.all_sessions().unwrap()[0].category, "Updated");
+
+        // The stale record is no longer the newest write for this uuid, so
+        // re-applying it must not clobber the newer category.
+        assert_eq!(destination.apply_incoming(&[stale]).unwrap(), 0);
+        assert_eq!(destination.all_sessions().unwrap()[0].category, "Updated");
+    }
+}
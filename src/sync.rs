@@ -0,0 +1,274 @@
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// A single synced session, exchanged between devices as one line of
+/// newline-delimited JSON. Carries the identity (`id`, `device_id`,
+/// `updated_at`) a [`crate::store::UsageStats`] needs to merge a remote
+/// history into the local one with last-writer-wins semantics, on top of
+/// the same fields recorded in the `sessions` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub id: String,
+    pub device_id: String,
+    pub updated_at: DateTime<Local>,
+    pub start_time: DateTime<Local>,
+    pub end_time: DateTime<Local>,
+    pub duration_seconds: f64,
+    pub app_name: String,
+    pub bundle_id: String,
+    pub category: String,
+    pub url: String,
+    pub title: String,
+}
+
+/// Where synced records are exchanged. A target only needs to support
+/// "give me everything" and "here's a batch to add" — `UsageStats`
+/// handles merging, so a target is just dumb storage.
+pub trait SyncTarget {
+    fn pull(&self) -> Result<Vec<SyncRecord>, String>;
+    fn push(&self, records: &[SyncRecord]) -> Result<(), String>;
+}
+
+/// Exchanges records as `.ndjson` files in a plain directory — a shared
+/// folder (Dropbox/iCloud/NFS share) that every device mounts. Each push
+/// writes a new timestamped file rather than rewriting a shared one, so
+/// concurrent syncs from different devices can't clobber each other.
+pub struct DirectorySyncTarget {
+    dir: PathBuf,
+}
+
+impl DirectorySyncTarget {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl SyncTarget for DirectorySyncTarget {
+    fn pull(&self) -> Result<Vec<SyncRecord>, String> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| format!("Failed to read sync directory {}: {}", self.dir.display(), e))?;
+
+        let mut records = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("Failed to read sync directory entry: {}", e))?
+                .path();
+
+            if path.extension().and_then(|ext| ext.to_str()) != Some("ndjson") {
+                continue;
+            }
+
+            let contents = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read sync file {}: {}", path.display(), e))?;
+            records.extend(parse_ndjson(&contents)?);
+        }
+
+        Ok(records)
+    }
+
+    fn push(&self, records: &[SyncRecord]) -> Result<(), String> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create sync directory {}: {}", self.dir.display(), e))?;
+
+        let file_name = format!("{}-{}.ndjson", records[0].device_id, Local::now().timestamp());
+        fs::write(self.dir.join(file_name), to_ndjson(records)?)
+            .map_err(|e| format!("Failed to write sync file: {}", e))
+    }
+}
+
+/// Exchanges records as objects under a prefix in an S3-compatible
+/// object store, addressed directly by HTTPS URL (a bucket's virtual-host
+/// endpoint, a presigned base URL, or any S3-compatible gateway) rather
+/// than a full AWS SDK, since listing/reading/writing flat `.ndjson`
+/// objects is all this needs.
+pub struct S3SyncTarget {
+    base_url: String,
+}
+
+impl S3SyncTarget {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Pulls out every `<Key>...</Key>` from an S3 `ListObjectsV2` XML
+    /// response. Good enough for flat `.ndjson` listings without pulling
+    /// in an XML parser.
+    fn keys_from_listing(xml: &str) -> Vec<String> {
+        let mut keys = Vec::new();
+        let mut rest = xml;
+
+        while let Some(start) = rest.find("<Key>") {
+            let after_start = &rest[start + "<Key>".len()..];
+            let Some(end) = after_start.find("</Key>") else {
+                break;
+            };
+            keys.push(after_start[..end].to_string());
+            rest = &after_start[end + "</Key>".len()..];
+        }
+
+        keys
+    }
+}
+
+impl SyncTarget for S3SyncTarget {
+    fn pull(&self) -> Result<Vec<SyncRecord>, String> {
+        let listing_url = format!("{}?list-type=2", self.base_url);
+        let listing = ureq::get(&listing_url)
+            .call()
+            .map_err(|e| format!("Failed to list sync objects at {}: {}", self.base_url, e))?
+            .into_string()
+            .map_err(|e| format!("Failed to read sync object listing: {}", e))?;
+
+        let mut records = Vec::new();
+        for key in Self::keys_from_listing(&listing) {
+            if !key.ends_with(".ndjson") {
+                continue;
+            }
+
+            let object_url = format!("{}/{}", self.base_url, key);
+            let body = ureq::get(&object_url)
+                .call()
+                .map_err(|e| format!("Failed to fetch sync object {}: {}", key, e))?
+                .into_string()
+                .map_err(|e| format!("Failed to read sync object {}: {}", key, e))?;
+
+            records.extend(parse_ndjson(&body)?);
+        }
+
+        Ok(records)
+    }
+
+    fn push(&self, records: &[SyncRecord]) -> Result<(), String> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let object_url = format!(
+            "{}/{}-{}.ndjson",
+            self.base_url,
+            records[0].device_id,
+            Local::now().timestamp()
+        );
+
+        ureq::put(&object_url)
+            .send_string(&to_ndjson(records)?)
+            .map_err(|e| format!("Failed to upload sync object {}: {}", object_url, e))?;
+
+        Ok(())
+    }
+}
+
+fn parse_ndjson(contents: &str) -> Result<Vec<SyncRecord>, String> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| format!("Failed to parse sync record: {}", e)))
+        .collect()
+}
+
+fn to_ndjson(records: &[SyncRecord]) -> Result<String, String> {
+    let mut out = String::new();
+    for record in records {
+        out.push_str(
+            &serde_json::to_string(record).map_err(|e| format!("Failed to serialize sync record: {}", e))?,
+        );
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Picks a [`SyncTarget`] from a CLI argument: an `s3://bucket/prefix`
+/// shorthand or a bare `http(s)://` base URL is treated as an
+/// S3-compatible object store, anything else as a directory path.
+pub fn target_from_arg(arg: &str) -> Box<dyn SyncTarget> {
+    if let Some(rest) = arg.strip_prefix("s3://") {
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        let mut base_url = format!("https://{}.s3.amazonaws.com", bucket);
+        if !prefix.is_empty() {
+            base_url.push('/');
+            base_url.push_str(prefix);
+        }
+        Box::new(S3SyncTarget::new(base_url))
+    } else if arg.starts_with("http://") || arg.starts_with("https://") {
+        Box::new(S3SyncTarget::new(arg.to_string()))
+    } else {
+        Box::new(DirectorySyncTarget::new(PathBuf::from(arg)))
+    }
+}
+
+/// Returns a UUID that's stable for this machine, generating and
+/// persisting one under the config directory on first use — the same
+/// `load_or_default` shape as [`crate::config::Config`].
+pub fn local_device_id() -> String {
+    let Some(path) = device_state_path("device_id") else {
+        return Uuid::new_v4().to_string();
+    };
+
+    if let Ok(existing) = fs::read_to_string(&path) {
+        let id = existing.trim();
+        if !id.is_empty() {
+            return id.to_string();
+        }
+    }
+
+    let id = Uuid::new_v4().to_string();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(&path, &id);
+    id
+}
+
+/// Returns the time the last sync with `target` that pushed successfully
+/// began (taken before its pull), or the Unix epoch if this device has
+/// never synced with it.
+/// Scoped per target so syncing with one machine/bucket doesn't advance
+/// the cursor for another and skip sessions that were still outgoing to
+/// it.
+pub fn last_sync_time(target: &str) -> DateTime<Local> {
+    let Some(path) = device_state_path(&last_sync_file_name(target)) else {
+        return Local.timestamp_opt(0, 0).unwrap();
+    };
+
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| DateTime::parse_from_rfc3339(contents.trim()).ok())
+        .map(|dt| dt.with_timezone(&Local))
+        .unwrap_or_else(|| Local.timestamp_opt(0, 0).unwrap())
+}
+
+pub fn save_last_sync_time(target: &str, time: DateTime<Local>) {
+    if let Some(path) = device_state_path(&last_sync_file_name(target)) {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let _ = fs::write(&path, time.to_rfc3339());
+    }
+}
+
+/// Turns a sync target (a directory path or URL) into a filesystem-safe
+/// name so each target gets its own last-sync cursor file.
+fn last_sync_file_name(target: &str) -> String {
+    let sanitized: String = target
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    format!("last_sync-{}", sanitized)
+}
+
+fn device_state_path(file_name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("activity_tracker").join(file_name))
+}
@@ -0,0 +1,207 @@
+use crate::cdp::CdpClient;
+use crate::config::Config;
+use crate::models::ActiveEntity;
+use crate::platform::{self, PlatformMonitor};
+use crate::state::SharedAppState;
+use crate::store::UsageStats;
+use chrono::Local;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// A notable transition emitted by the tracking loop. Consumed by the
+/// GUI's activity log panel; the headless binary ignores these and prints
+/// directly instead.
+#[derive(Debug, Clone)]
+pub enum TrackerEvent {
+    // Only read by the optional `gui` feature's activity log panel, so the
+    // fields are unused (and clippy flags them as dead code) in a headless
+    // build.
+    #[allow(dead_code)]
+    Switched {
+        from: Option<ActiveEntity>,
+        to: Option<ActiveEntity>,
+    },
+}
+
+/// Returns `(url, title)` for the active browser tab, preferring the
+/// Chrome DevTools Protocol (faster, richer, version-stable across
+/// Chromium-based browsers) and falling back to the platform monitor's
+/// native mechanism when no debugging port was configured or the browser
+/// isn't reachable over it.
+fn get_browser_tab_info(
+    entity: &ActiveEntity,
+    cdp_client: Option<&CdpClient>,
+    monitor: &dyn PlatformMonitor,
+) -> (Option<String>, Option<String>) {
+    if let Some(client) = cdp_client {
+        if let Some((url, title)) = client.active_page() {
+            return (Some(url), title);
+        }
+    }
+
+    (monitor.browser_url(entity), None)
+}
+
+/// Bundle id of the distinguished pseudo-entity recorded while the user is
+/// away from keyboard/mouse, so AFK time is never attributed to whatever
+/// app happened to be focused when they left.
+const IDLE_BUNDLE_ID: &str = "system.idle";
+
+const DEFAULT_IDLE_THRESHOLD_SECS: u64 = 60;
+
+fn idle_entity() -> ActiveEntity {
+    ActiveEntity {
+        bundle_id: IDLE_BUNDLE_ID.to_string(),
+        name: "Idle".to_string(),
+        url: None,
+        title: None,
+        category: Some("Idle".to_string()),
+    }
+}
+
+/// Reads `--idle-threshold-secs=<n>` from the process arguments, falling
+/// back to `DEFAULT_IDLE_THRESHOLD_SECS` when absent or unparsable.
+pub fn idle_threshold_from_args() -> Duration {
+    std::env::args()
+        .find_map(|arg| arg.strip_prefix("--idle-threshold-secs=").and_then(|s| s.parse().ok()))
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_IDLE_THRESHOLD_SECS))
+}
+
+/// Polls the foreground app/browser tab until `running` is cleared,
+/// recording each completed session to `usage_stats` and folding it into
+/// `state`. When `events` is set, every app switch is also published so a
+/// GUI can show an activity log without touching stdout.
+pub fn run_loop(
+    usage_stats: &UsageStats,
+    cdp_client: Option<CdpClient>,
+    config: &Config,
+    running: Arc<AtomicBool>,
+    state: SharedAppState,
+    events: Option<Sender<TrackerEvent>>,
+    quiet: bool,
+) {
+    let monitor = platform::current();
+    let idle_threshold = idle_threshold_from_args();
+    let mut current_entity: Option<ActiveEntity> = None;
+    let mut session_start_time = Local::now();
+
+    while running.load(Ordering::SeqCst) {
+        let idle_secs = monitor.idle_seconds().unwrap_or(0.0);
+        let is_idle = idle_secs >= idle_threshold.as_secs_f64();
+
+        let new_active_entity = if is_idle {
+            Some(idle_entity())
+        } else {
+            monitor.active_window().map(|mut entity| {
+                match entity.bundle_id.as_str() {
+                    "com.google.Chrome"
+                    | "com.google.Chrome.canary"
+                    | "com.apple.Safari"
+                    | "com.brave.Browser"
+                    | "com.microsoft.edgemac" => {
+                        let (url, title) =
+                            get_browser_tab_info(&entity, cdp_client.as_ref(), monitor.as_ref());
+                        entity.category = config.categorize(&entity.bundle_id, url.as_deref());
+                        entity.url = config.redact_url(url);
+                        entity.title = title;
+                    }
+                    _ => {
+                        entity.category = config.categorize(&entity.bundle_id, None);
+                    }
+                }
+
+                entity
+            })
+        };
+
+        if current_entity != new_active_entity {
+            // Switched app/URL (or went idle/came back)
+            let now = Local::now();
+            let mut next_session_start_time = now;
+
+            if let Some(ref entity) = current_entity {
+                let wall_duration = (now - session_start_time).to_std().unwrap_or(Duration::ZERO);
+
+                // When we just detected idleness, part of the session's
+                // wall-clock span was actually AFK time; don't credit it
+                // to the app that happened to be focused before the user
+                // left.
+                let idle_overhang = if is_idle {
+                    Duration::from_secs_f64(idle_secs).min(wall_duration)
+                } else {
+                    Duration::ZERO
+                };
+                let active_duration = wall_duration.saturating_sub(idle_overhang);
+                let session_end_time =
+                    now - chrono::Duration::from_std(idle_overhang).unwrap_or_default();
+                // The Idle session (or the next app's session) picks up
+                // exactly where this one left off, so the detection lag
+                // baked into idle_overhang isn't lost between the two.
+                next_session_start_time = session_end_time;
+
+                if active_duration > Duration::ZERO {
+                    if let Err(e) = usage_stats.add_session(
+                        entity,
+                        session_start_time,
+                        session_end_time,
+                        active_duration,
+                    ) {
+                        eprintln!("Warning: Failed to record session: {}", e);
+                    }
+
+                    if let Ok(mut state) = state.write() {
+                        state.record_session(entity, active_duration);
+                    }
+                }
+
+                if !quiet {
+                    println!(
+                        "Switched from: {:?} (spent: {:.2?})",
+                        entity, active_duration
+                    );
+                }
+            }
+
+            if let Some(tx) = &events {
+                let _ = tx.send(TrackerEvent::Switched {
+                    from: current_entity.clone(),
+                    to: new_active_entity.clone(),
+                });
+            }
+
+            current_entity = new_active_entity;
+            session_start_time = next_session_start_time;
+
+            if let Ok(mut state) = state.write() {
+                state.set_current(current_entity.clone());
+            }
+
+            if let Some(ref entity) = current_entity {
+                if !quiet {
+                    println!("Started tracking: {:?}", entity);
+                }
+            }
+        }
+
+        thread::sleep(Duration::from_secs(2));
+    }
+
+    // Save final session if there is one
+    if let Some(ref entity) = current_entity {
+        let final_exit_time = Local::now();
+        let duration_spent_on_last_entity = final_exit_time.signed_duration_since(session_start_time);
+        let duration = Duration::from_secs(duration_spent_on_last_entity.num_seconds() as u64);
+
+        if let Err(e) = usage_stats.add_session(entity, session_start_time, final_exit_time, duration) {
+            eprintln!("Warning: Failed to record final session: {}", e);
+        }
+
+        if let Ok(mut state) = state.write() {
+            state.record_session(entity, duration);
+        }
+    }
+}